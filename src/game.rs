@@ -1,7 +1,22 @@
-use serde::{Deserialize, Serialize};
 use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
+use std::collections::{HashMap, HashSet, VecDeque};
+
+use crate::world::{Effect, ItemDef, StatKind, World};
+
+/// How often (in seconds) hunger and thirst tick upward.
+pub const TICK_SECONDS: i64 = 60;
+/// Hunger/thirst increase applied per tick.
+const URGE_INCREMENT: i32 = 5;
+/// Hunger/thirst are clamped to this maximum.
+const URGE_MAX: i32 = 100;
+/// Once hunger or thirst reaches this level, it starts draining health.
+const URGE_DANGER_THRESHOLD: i32 = 80;
+/// Health lost per tick while starving or dehydrated.
+const STARVATION_DAMAGE: i32 = 2;
+
 /// Core game state returned to the client / gateway
 #[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct GameState {
@@ -10,15 +25,44 @@ pub struct GameState {
     pub log: Vec<String>,
     pub game_over: bool,
     pub victory: bool,
+    /// Unix timestamp of the last hunger/thirst tick applied to this game.
+    pub last_tick: i64,
+    /// Current health of each NPC the player has fought, keyed by NPC id.
+    pub npc_health: HashMap<String, i32>,
+    /// How many of each creature the player has slain, keyed by NPC name.
+    pub kills: HashMap<String, u32>,
+    /// Loot dropped on the ground by defeated NPCs, available to pick up.
+    pub dropped: Vec<DroppedLoot>,
+    /// Actions queued by `Enqueue` for multi-step sequences, drained one at a
+    /// time by `Step`. `GoTo` does not use this queue.
+    pub pending: VecDeque<GameAction>,
+    /// Monotonically increasing version of this state. Clients can pass the
+    /// last `seq` they saw back as a `since_seq` cursor to replay whatever
+    /// `Update`s they missed, e.g. after a dropped connection.
+    pub seq: u64,
 }
 
-/// Player position & stats
+/// Item names dropped at a tile, e.g. loot from a defeated NPC.
 #[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct DroppedLoot {
+    pub x: i32,
+    pub y: i32,
+    pub items: Vec<String>,
+}
+
+/// Player position & stats
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
 pub struct PlayerState {
     pub x: i32,
     pub y: i32,
     pub health: i32,
+    pub attack: i32,
+    pub defense: i32,
     pub inventory: Vec<String>,
+    /// 0 = fine, rising toward `URGE_MAX` as the player goes without food.
+    pub hunger: i32,
+    /// 0 = fine, rising toward `URGE_MAX` as the player goes without water.
+    pub thirst: i32,
 }
 
 /// Actions that can be requested by the gateway / LLM
@@ -35,10 +79,23 @@ pub enum GameAction {
     UseItem { item: String },
     #[serde(rename = "attack")]
     Attack,
+    #[serde(rename = "eat")]
+    Eat { item: String },
+    #[serde(rename = "drink")]
+    Drink { item: String },
+    /// Path-plan to `(x, y)` over the world graph and walk there in one call.
+    #[serde(rename = "go_to")]
+    GoTo { x: i32, y: i32 },
+    /// Queue a sequence of actions to be run one at a time via `Step`.
+    #[serde(rename = "enqueue")]
+    Enqueue { actions: Vec<GameAction> },
+    /// Run the next queued action, if any.
+    #[serde(rename = "step")]
+    Step,
 }
 
 /// Directions for movement
-#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "lowercase")]
 pub enum Direction {
     North,
@@ -47,60 +104,227 @@ pub enum Direction {
     West,
 }
 
-/// Create a brand new game
-pub fn new_game() -> GameState {
+/// Create a brand new game, with the hunger/thirst clock starting at `now`.
+pub fn new_game(now: i64) -> GameState {
     GameState {
         game_id: Uuid::new_v4().to_string(),
         player: PlayerState {
             x: 0,
             y: 0,
             health: 10,
+            attack: 4,
+            defense: 1,
             inventory: vec![],
+            hunger: 0,
+            thirst: 0,
         },
         log: vec![
             "You wake up in a small village at (0,0). To the east lies a dark forest.".to_string(),
         ],
         game_over: false,
         victory: false,
+        last_tick: now,
+        npc_health: HashMap::new(),
+        kills: HashMap::new(),
+        dropped: vec![],
+        pending: VecDeque::new(),
+        seq: 0,
+    }
+}
+
+/// Advance hunger/thirst by however many whole ticks have elapsed since
+/// `state.last_tick`, draining health once either urge crosses the danger
+/// threshold. `last_tick` only advances by whole ticks consumed, so partial
+/// elapsed time carries over to the next call.
+pub fn tick(state: &mut GameState, now: i64) {
+    if state.game_over {
+        return;
+    }
+
+    let elapsed_ticks = (now - state.last_tick) / TICK_SECONDS;
+    if elapsed_ticks <= 0 {
+        return;
+    }
+
+    for _ in 0..elapsed_ticks {
+        state.player.hunger = (state.player.hunger + URGE_INCREMENT).min(URGE_MAX);
+        state.player.thirst = (state.player.thirst + URGE_INCREMENT).min(URGE_MAX);
+
+        if state.player.hunger >= URGE_DANGER_THRESHOLD
+            || state.player.thirst >= URGE_DANGER_THRESHOLD
+        {
+            state.player.health -= STARVATION_DAMAGE;
+            state
+                .log
+                .push("Hunger and thirst gnaw at you, draining your health.".to_string());
+
+            if state.player.health <= 0 {
+                state.player.health = 0;
+                state.log.push(
+                    "Weakened by hunger and thirst, you collapse and don't get up.".to_string(),
+                );
+                state.game_over = true;
+                state.victory = false;
+                break;
+            }
+        }
     }
+
+    state.last_tick += elapsed_ticks * TICK_SECONDS;
+    state.seq += 1;
 }
 
 /// Apply an action to the state and return an updated copy
-pub fn apply_action(state: &GameState, action: &GameAction) -> GameState {
+pub fn apply_action(state: &GameState, action: &GameAction, world: &World, now: i64) -> GameState {
     // Clone so we keep the original immutable
     let mut new_state = state.clone();
 
+    let was_already_over = new_state.game_over;
+    tick(&mut new_state, now);
+
     if new_state.game_over {
-        new_state
-            .log
-            .push("The game is already over. Start a new one to continue playing.".to_string());
+        if was_already_over {
+            new_state
+                .log
+                .push("The game is already over. Start a new one to continue playing.".to_string());
+            new_state.seq += 1;
+        }
         return new_state;
     }
 
+    dispatch(&mut new_state, action, world);
+    new_state.seq += 1;
+
+    new_state
+}
+
+/// Run a single action against `state`, routing the queue-related variants
+/// (`GoTo`, `Enqueue`, `Step`) to their own handlers and everything else to
+/// its existing per-action handler.
+fn dispatch(state: &mut GameState, action: &GameAction, world: &World) {
     match action {
-        GameAction::Move { direction } => handle_move(&mut new_state, direction),
-        GameAction::Inspect => handle_inspect(&mut new_state),
-        GameAction::Pickup => handle_pickup(&mut new_state),
-        GameAction::UseItem { item } => handle_use_item(&mut new_state, item),
-        GameAction::Attack => handle_attack(&mut new_state),
+        GameAction::Move { direction } => handle_move(state, direction, world),
+        GameAction::Inspect => handle_inspect(state, world),
+        GameAction::Pickup => handle_pickup(state, world),
+        GameAction::UseItem { item } => handle_use_item(state, item, world),
+        GameAction::Attack => handle_attack(state, world),
+        GameAction::Eat { item } => handle_eat(state, item, world),
+        GameAction::Drink { item } => handle_drink(state, item, world),
+        GameAction::GoTo { x, y } => handle_go_to(state, *x, *y, world),
+        GameAction::Enqueue { actions } => {
+            state.pending.extend(actions.iter().cloned());
+        }
+        GameAction::Step => drain_step(state, world),
     }
+}
 
-    new_state
+/// Path-plan to `(x, y)` and walk it in one call, stopping early if a move
+/// is blocked or the game ends. Runs the planned moves
+/// directly rather than going through `state.pending`, so a `go_to` never
+/// drains (and can't accidentally run) whatever an earlier `Enqueue` left
+/// sitting in the queue.
+fn handle_go_to(state: &mut GameState, x: i32, y: i32, world: &World) {
+    let Some(path) = plan_path(world, (state.player.x, state.player.y), (x, y)) else {
+        state
+            .log
+            .push(format!("You can't find a way to ({},{}) from here.", x, y));
+        return;
+    };
+
+    for direction in path {
+        let logged_before = state.log.len();
+        handle_move(state, &direction, world);
+        if state.game_over || should_halt_queue(&state.log[logged_before..]) {
+            break;
+        }
+    }
+}
+
+/// Run the next queued action, if any.
+fn drain_step(state: &mut GameState, world: &World) {
+    match state.pending.pop_front() {
+        Some(next) => dispatch(state, &next, world),
+        None => state.log.push("There's nothing queued to do.".to_string()),
+    }
 }
 
-fn handle_move(state: &mut GameState, direction: &Direction) {
-    let (dx, dy) = match direction {
+/// Whether the log lines a move just appended should stop auto-travel:
+/// a blocked move. `handle_go_to` only ever dispatches
+/// `handle_move`, which can't trigger combat, so this doesn't check for it;
+/// revisit if auto-travel starts risking encounters along the way.
+fn should_halt_queue(new_lines: &[String]) -> bool {
+    new_lines
+        .iter()
+        .any(|line| line.starts_with("You can't go that way"))
+}
+
+/// Find a shortest path of moves from `start` to `goal` over the world's
+/// tile exits via BFS, or `None` if no such path exists.
+fn plan_path(world: &World, start: (i32, i32), goal: (i32, i32)) -> Option<Vec<Direction>> {
+    if start == goal {
+        return Some(vec![]);
+    }
+
+    let mut visited = HashSet::new();
+    let mut came_from: HashMap<(i32, i32), ((i32, i32), Direction)> = HashMap::new();
+    let mut queue = VecDeque::new();
+    queue.push_back(start);
+    visited.insert(start);
+
+    while let Some(current) = queue.pop_front() {
+        let Some(tile) = world.tile_at(current.0, current.1) else {
+            continue;
+        };
+
+        for direction in &tile.exits {
+            let (dx, dy) = direction_delta(direction);
+            let next = (current.0 + dx, current.1 + dy);
+            if visited.contains(&next) || world.tile_at(next.0, next.1).is_none() {
+                continue;
+            }
+            visited.insert(next);
+            came_from.insert(next, (current, direction.clone()));
+
+            if next == goal {
+                let mut steps = vec![direction.clone()];
+                let mut at = current;
+                while let Some((prev, dir)) = came_from.get(&at) {
+                    steps.push(dir.clone());
+                    at = *prev;
+                }
+                steps.reverse();
+                return Some(steps);
+            }
+
+            queue.push_back(next);
+        }
+    }
+
+    None
+}
+
+/// The `(dx, dy)` a step in `direction` moves the player.
+fn direction_delta(direction: &Direction) -> (i32, i32) {
+    match direction {
         Direction::North => (0, -1),
         Direction::South => (0, 1),
         Direction::East => (1, 0),
         Direction::West => (-1, 0),
-    };
+    }
+}
+
+fn handle_move(state: &mut GameState, direction: &Direction, world: &World) {
+    let (dx, dy) = direction_delta(direction);
 
     let new_x = state.player.x + dx;
     let new_y = state.player.y + dy;
 
-    // World bounds: 0..=2 for x, 0..=1 for y
-    if new_x < 0 || new_x > 2 || new_y < 0 || new_y > 1 {
+    let can_move = world
+        .tile_at(state.player.x, state.player.y)
+        .is_some_and(|t| t.exits.contains(direction))
+        && world.tile_at(new_x, new_y).is_some();
+
+    if !can_move {
         state
             .log
             .push("You can't go that way. The world seems to end there.".to_string());
@@ -110,151 +334,282 @@ fn handle_move(state: &mut GameState, direction: &Direction) {
     state.player.x = new_x;
     state.player.y = new_y;
 
-    let desc = describe_tile(new_x, new_y);
+    let desc = world.describe(new_x, new_y);
     state
         .log
         .push(format!("You move to ({},{}) - {}", new_x, new_y, desc));
 }
 
-fn handle_inspect(state: &mut GameState) {
-    let desc = describe_tile(state.player.x, state.player.y);
+fn handle_inspect(state: &mut GameState, world: &World) {
+    let desc = world.describe(state.player.x, state.player.y);
     state
         .log
         .push(format!("You inspect your surroundings: {}", desc));
 }
 
-fn handle_pickup(state: &mut GameState) {
+fn handle_pickup(state: &mut GameState, world: &World) {
     let (x, y) = (state.player.x, state.player.y);
 
-    // Very simple item logic:
-    // - Forest at (1,0) has a "potion"
-    // - Cave entrance at (2,0) has a "rusty key"
-    match (x, y) {
-        (1, 0) => {
-            if !state.player.inventory.contains(&"potion".to_string()) {
-                state.player.inventory.push("potion".to_string());
-                state
-                    .log
-                    .push("You find a small potion on the ground and pick it up.".to_string());
-            } else {
-                state
-                    .log
-                    .push("You already picked up the potion here.".to_string());
+    if let Some(index) = state.dropped.iter().position(|d| d.x == x && d.y == y) {
+        let slot = &mut state.dropped[index];
+        if let Some(item_name) = slot.items.pop() {
+            if slot.items.is_empty() {
+                state.dropped.remove(index);
             }
+            state
+                .log
+                .push(format!("You pick up the {} left behind.", item_name));
+            state.player.inventory.push(item_name);
+            return;
         }
-        (2, 0) => {
-            if !state.player.inventory.contains(&"rusty key".to_string()) {
-                state.player.inventory.push("rusty key".to_string());
-                state.log.push(
-                    "You notice a rusty key wedged between rocks and carefully take it."
-                        .to_string(),
-                );
-            } else {
-                state
-                    .log
-                    .push("You already picked up the key here.".to_string());
-            }
+    }
+
+    let tile_items = world
+        .tile_at(x, y)
+        .map(|t| t.items.as_slice())
+        .unwrap_or(&[]);
+    match tile_items
+        .iter()
+        .find(|item| !state.player.inventory.contains(&item.name))
+    {
+        Some(item) => {
+            state.player.inventory.push(item.name.clone());
+            state.log.push(item.pickup_text.clone());
         }
-        _ => {
+        None if tile_items.is_empty() => {
             state
                 .log
                 .push("You search around but don't find anything interesting.".to_string());
         }
+        None => {
+            state
+                .log
+                .push("You've already picked up everything here.".to_string());
+        }
     }
 }
 
-fn handle_use_item(state: &mut GameState, item: &str) {
+fn handle_use_item(state: &mut GameState, item: &str, world: &World) {
     if !state.player.inventory.contains(&item.to_string()) {
+        state.log.push(format!("You don't have a {} to use.", item));
+        return;
+    }
+
+    let Some(def) = world.item_by_name(item).cloned() else {
         state
             .log
-            .push(format!("You don't have a {} to use.", item));
+            .push(format!("You can't figure out how to use the {}.", item));
         return;
+    };
+
+    if let Some(location) = def.use_requires {
+        if state.player.x != location.x || state.player.y != location.y {
+            state.log.push(
+                def.use_elsewhere_text
+                    .clone()
+                    .unwrap_or_else(|| format!("Nothing happens when you use the {} here.", item)),
+            );
+            return;
+        }
+
+        if let Some(npc) = world.npc_at(location.x, location.y) {
+            let npc_alive = state.npc_health.get(&npc.id).copied().unwrap_or(npc.health) > 0;
+            if npc_alive {
+                state.log.push(format!(
+                    "The {} blocks your way; you can't use the {} while it still guards this place.",
+                    npc.name, item
+                ));
+                return;
+            }
+        }
     }
 
-    match item {
-        "potion" => {
-            state.player.health = 10;
-            state
-                .player
-                .inventory
-                .retain(|i| i != "potion"); // consume potion
-            state
-                .log
-                .push("You drink the potion. Your health is fully restored.".to_string());
+    if def.effects.is_empty() {
+        state
+            .log
+            .push(format!("You can't figure out how to use the {}.", item));
+        return;
+    }
+
+    state.log.extend(def.use_text.iter().cloned());
+    for effect in &def.effects {
+        apply_effect(state, effect, &def);
+    }
+}
+
+fn apply_effect(state: &mut GameState, effect: &Effect, item: &ItemDef) {
+    match effect {
+        Effect::ChangeParameter {
+            param,
+            delta,
+            clamp,
+        } => {
+            change_stat(&mut state.player, *param, *delta, *clamp);
         }
-        "rusty key" => {
-            if state.player.x == 2 && state.player.y == 1 {
-                state.log.push(
-                    "You use the rusty key to open the ancient chest in the cave.".to_string(),
-                );
-                state.log.push(
-                    "Inside, you find a pile of gold and a glowing gem. You have found the treasure!"
-                        .to_string(),
-                );
-                state.game_over = true;
-                state.victory = true;
-            } else {
-                state.log.push(
-                    "You idly play with the rusty key, but it doesn't seem to fit anything here."
-                        .to_string(),
-                );
+        Effect::ConsumeSelf => {
+            state.player.inventory.retain(|i| i != &item.name);
+        }
+        Effect::UnlockAt { x, y, reveals } => {
+            if state.player.x == *x && state.player.y == *y {
+                for revealed in reveals {
+                    state.log.push(revealed.pickup_text.clone());
+                    state.player.inventory.push(revealed.name.clone());
+                }
             }
         }
-        _ => {
-            state
-                .log
-                .push(format!("You can't figure out how to use the {}.", item));
+        Effect::GrantVictory => {
+            state.game_over = true;
+            state.victory = true;
         }
     }
 }
 
-fn handle_attack(state: &mut GameState) {
-    // Only meaningful in the deep cave at (2,1)
+fn change_stat(player: &mut PlayerState, param: StatKind, delta: i32, clamp: Option<(i32, i32)>) {
+    let stat = match param {
+        StatKind::Health => &mut player.health,
+        StatKind::Hunger => &mut player.hunger,
+        StatKind::Thirst => &mut player.thirst,
+    };
+    *stat += delta;
+    if let Some((min, max)) = clamp {
+        *stat = (*stat).clamp(min, max);
+    }
+}
+
+/// Whether any of the item's effects would lower `param`, i.e. whether
+/// eating/drinking it actually addresses that urge.
+fn eases(def: &ItemDef, param: StatKind) -> bool {
+    def.effects.iter().any(|effect| {
+        matches!(effect, Effect::ChangeParameter { param: p, delta, .. } if *p == param && *delta < 0)
+    })
+}
+
+/// Eat `item`, running all of its effects through `apply_effect` (not just
+/// the hunger one), so a food item that also grants loot or carries a
+/// non-default clamp behaves the same way `handle_use_item` would apply it.
+fn handle_eat(state: &mut GameState, item: &str, world: &World) {
+    if !state.player.inventory.contains(&item.to_string()) {
+        state.log.push(format!("You don't have a {} to eat.", item));
+        return;
+    }
+
+    let Some(def) = world.item_by_name(item).cloned() else {
+        state.log.push(format!("The {} doesn't look edible.", item));
+        return;
+    };
+
+    if !eases(&def, StatKind::Hunger) {
+        state.log.push(format!("The {} doesn't look edible.", item));
+        return;
+    }
+
+    state
+        .log
+        .push(format!("You eat the {}. Your hunger eases.", item));
+    for effect in &def.effects {
+        apply_effect(state, effect, &def);
+    }
+}
+
+/// Drink `item`, running all of its effects through `apply_effect` (not
+/// just the thirst one), so a drink that also grants loot or carries a
+/// non-default clamp behaves the same way `handle_use_item` would apply it.
+fn handle_drink(state: &mut GameState, item: &str, world: &World) {
+    if !state.player.inventory.contains(&item.to_string()) {
+        state
+            .log
+            .push(format!("You don't have a {} to drink.", item));
+        return;
+    }
+
+    let Some(def) = world.item_by_name(item).cloned() else {
+        state
+            .log
+            .push(format!("The {} isn't something you can drink.", item));
+        return;
+    };
+
+    if !eases(&def, StatKind::Thirst) {
+        state
+            .log
+            .push(format!("The {} isn't something you can drink.", item));
+        return;
+    }
+
+    state
+        .log
+        .push(format!("You drink the {}. Your thirst eases.", item));
+    for effect in &def.effects {
+        apply_effect(state, effect, &def);
+    }
+}
+
+fn handle_attack(state: &mut GameState, world: &World) {
     let (x, y) = (state.player.x, state.player.y);
-    if (x, y) != (2, 1) {
+    let Some(npc) = world.npc_at(x, y) else {
         state
             .log
             .push("You swing at the air. There's nothing to attack here.".to_string());
         return;
-    }
+    };
+    let npc = npc.clone();
 
-    // Simple "combat": 50/50 chance to win or take damage
-    let roll = fastrand::u8(0..=100);
-    if roll < 50 {
+    let mut npc_health = state.npc_health.get(&npc.id).copied().unwrap_or(npc.health);
+    if npc_health <= 0 {
         state
             .log
-            .push("You lunge forward and strike the lurking shadow. It vanishes!".to_string());
-        state.log.push(
-            "With the guardian defeated, you can now safely search for treasure here."
-                .to_string(),
-        );
+            .push(format!("The {} already lies defeated.", npc.name));
+        return;
+    }
+
+    let spread = fastrand::i32(-1..=1);
+    let player_damage = (state.player.attack - npc.defense + spread).max(1);
+    npc_health -= player_damage;
+    state.log.push(format!(
+        "You strike the {} for {} damage.",
+        npc.name, player_damage
+    ));
+
+    if npc_health <= 0 {
+        state.log.push(format!("The {} is defeated!", npc.name));
+        *state.kills.entry(npc.name.clone()).or_insert(0) += 1;
+
+        if !npc.loot.is_empty() {
+            state
+                .log
+                .push(format!("It drops {} onto the ground.", npc.loot.join(", ")));
+            match state.dropped.iter_mut().find(|d| d.x == x && d.y == y) {
+                Some(slot) => slot.items.extend(npc.loot.clone()),
+                None => state.dropped.push(DroppedLoot {
+                    x,
+                    y,
+                    items: npc.loot.clone(),
+                }),
+            }
+        }
     } else {
-        let damage = 3;
-        state.player.health -= damage;
-        state.log.push(format!(
-            "A dark creature lashes out from the shadows and hits you for {} damage!",
-            damage
-        ));
-
-        if state.player.health <= 0 {
-            state.log.push("You collapse to the ground. The darkness closes in...".to_string());
-            state.game_over = true;
-            state.victory = false;
+        let npc_damage = (npc.attack - state.player.defense).max(0);
+        if npc_damage > 0 {
+            state.player.health -= npc_damage;
+            state.log.push(format!(
+                "The {} retaliates, hitting you for {} damage.",
+                npc.name, npc_damage
+            ));
+
+            if state.player.health <= 0 {
+                state.player.health = 0;
+                state
+                    .log
+                    .push("You collapse to the ground. The darkness closes in...".to_string());
+                state.game_over = true;
+                state.victory = false;
+            }
         } else {
-            state.log.push("You barely survive the attack and stagger back.".to_string());
+            state
+                .log
+                .push(format!("The {} fails to land a hit.", npc.name));
         }
     }
-}
 
-/// Describe the tile at coordinates (x,y)
-fn describe_tile(x: i32, y: i32) -> &'static str {
-    match (x, y) {
-        (0, 0) => "a quiet village square with a well in the center.",
-        (1, 0) => "a dense forest. You hear distant howls and see something glinting on the ground.",
-        (2, 0) => "the entrance to a dark cave. Cold air flows from within.",
-        (2, 1) => "a deep cave chamber. You feel an ominous presence and see a locked chest.",
-        (0, 1) => "a small riverbank. The water is clear and cold.",
-        (1, 1) => "a rocky path leading between the forest and the cave.",
-        _ => "featureless terrain.",
-    }
+    state.npc_health.insert(npc.id.clone(), npc_health);
 }
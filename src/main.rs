@@ -1,28 +1,42 @@
 mod game;
+mod store;
+mod subscriptions;
+mod update;
+mod world;
 
-use crate::game::{apply_action, new_game, GameAction, GameState};
-use tracing::{info, error};
-use rmcp::model::{CallToolResult, Content, ErrorData, ServerCapabilities, ServerInfo};
+use crate::game::{apply_action, new_game, tick, GameAction, TICK_SECONDS};
+use crate::store::{GameStore, InMemoryStore, PostgresStore};
+use crate::subscriptions::SubscriptionRegistry;
+use crate::update::Update;
+use crate::world::World;
+use rmcp::model::{
+    AnnotateAble, CallToolResult, Content, ErrorData, ListResourcesResult, RawResource,
+    ReadResourceRequestParam, ReadResourceResult, ResourceContents, ServerCapabilities, ServerInfo,
+    SubscribeRequestParam, UnsubscribeRequestParam,
+};
+use rmcp::service::RequestContext;
 use rmcp::{
-    handler::server::router::tool::ToolRouter,
-    handler::server::wrapper::Parameters,
-    schemars,
-    tool,
-    tool_handler,
-    tool_router,
-    transport::stdio,
-    ServerHandler,
-    ServiceExt,
+    handler::server::router::tool::ToolRouter, handler::server::wrapper::Parameters, schemars,
+    tool, tool_handler, tool_router, transport::stdio, RoleServer, ServerHandler, ServiceExt,
 };
-use std::collections::HashMap;
 use std::error::Error;
 use std::sync::Arc;
-use tokio::sync::Mutex;
+use tracing::{error, info};
 use tracing_subscriber::FmtSubscriber;
 
 // Alias for convenience
 type McpError = ErrorData;
 
+/// Default location of the data file describing tiles, exits, and items.
+const DEFAULT_WORLD_PATH: &str = "assets/world.json";
+
+/// Current time as a unix timestamp, used to drive the hunger/thirst clock.
+fn now_unix() -> i64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .expect("system clock is before the unix epoch")
+        .as_secs() as i64
+}
 
 /// Parameters for `game_get_state`
 #[derive(Debug, serde::Deserialize, schemars::JsonSchema)]
@@ -37,34 +51,59 @@ pub struct ApplyActionParams {
     pub action: GameAction,
 }
 
+/// Parameters for `game_replay_updates`
+#[derive(Debug, serde::Deserialize, schemars::JsonSchema)]
+pub struct ReplayUpdatesParams {
+    pub game_id: String,
+    /// The last `GameState::seq` the client saw; updates after it are replayed.
+    pub since_seq: u64,
+}
+
+/// The URI scheme under which each game is exposed as an MCP resource.
+fn resource_uri(game_id: &str) -> String {
+    format!("game://{}", game_id)
+}
+
 #[derive(Clone)]
 pub struct TreasureEngine {
-    games: Arc<Mutex<HashMap<String, GameState>>>,
+    store: Arc<dyn GameStore>,
+    world: Arc<World>,
+    subscriptions: SubscriptionRegistry,
     tool_router: ToolRouter<Self>,
 }
 
 #[tool_router]
 impl TreasureEngine {
-    pub fn new() -> Self {
-        Self {
-            games: Arc::new(Mutex::new(HashMap::new())),
+    /// Build an engine backed by the world data at `world_path` and the given
+    /// store, notifying resource subscribers via `subscriptions` — shared
+    /// with the background ticker so ticks raise `Update`s too.
+    pub fn new(
+        world_path: impl AsRef<std::path::Path>,
+        store: Arc<dyn GameStore>,
+        subscriptions: SubscriptionRegistry,
+    ) -> Result<Self, Box<dyn Error>> {
+        Ok(Self {
+            store,
+            world: Arc::new(World::load(world_path)?),
+            subscriptions,
             tool_router: Self::tool_router(),
-        }
+        })
     }
 
     /// Start a new game and return the initial GameState
     #[tool(description = "Start a new Treasure Quest game and return the initial state")]
     async fn game_start(&self) -> Result<CallToolResult, McpError> {
         info!("Tool call: game_start - Input: (no parameters)");
-        let game = new_game();
-        let id = game.game_id.clone();
+        let game = new_game(now_unix());
 
-        let mut games = self.games.lock().await;
-        games.insert(id, game.clone());
+        self.store
+            .save(&game.game_id, &game)
+            .await
+            .map_err(|e| McpError::internal_error(e.to_string(), None))?;
 
         let content =
             Content::json(&game).map_err(|e| McpError::internal_error(e.to_string(), None))?;
-        
+
         info!("Tool call: game_start - Output: game_id={}", game.game_id);
         Ok(CallToolResult::success(vec![content]))
     }
@@ -78,12 +117,16 @@ impl TreasureEngine {
         let GetStateParams { game_id } = params.0;
         info!("Tool call: game_get_state - Input: game_id={}", game_id);
 
-        let games = self.games.lock().await;
-        let state = games
-            .get(&game_id)
-            .cloned()
+        let state = self
+            .store
+            .load(&game_id)
+            .await
+            .map_err(|e| McpError::internal_error(e.to_string(), None))?
             .ok_or_else(|| {
-                error!("Tool call: game_get_state - Error: No game found for id {}", game_id);
+                error!(
+                    "Tool call: game_get_state - Error: No game found for id {}",
+                    game_id
+                );
                 McpError::invalid_params(
                     format!("No game found for id {}", game_id),
                     None, // data
@@ -92,8 +135,11 @@ impl TreasureEngine {
 
         let content =
             Content::json(&state).map_err(|e| McpError::internal_error(e.to_string(), None))?;
-        
-        info!("Tool call: game_get_state - Output: returned state for game_id={}", game_id);
+
+        info!(
+            "Tool call: game_get_state - Output: returned state for game_id={}",
+            game_id
+        );
         Ok(CallToolResult::success(vec![content]))
     }
     /// Apply an action to the game and return the updated state
@@ -103,28 +149,63 @@ impl TreasureEngine {
         params: Parameters<ApplyActionParams>,
     ) -> Result<CallToolResult, McpError> {
         let ApplyActionParams { game_id, action } = params.0;
-        info!("Tool call: game_apply_action - Input: game_id={}, action={:?}", game_id, action);
+        info!(
+            "Tool call: game_apply_action - Input: game_id={}, action={:?}",
+            game_id, action
+        );
 
-        let mut games = self.games.lock().await;
-        let current = games
-            .get(&game_id)
-            .cloned()
+        let world = Arc::clone(&self.world);
+        let now = now_unix();
+        let (current, updated) = self
+            .store
+            .update(
+                &game_id,
+                Box::new(move |state| *state = apply_action(state, &action, &world, now)),
+            )
+            .await
+            .map_err(|e| McpError::internal_error(e.to_string(), None))?
             .ok_or_else(|| {
-                error!("Tool call: game_apply_action - Error: No game found for id {}", game_id);
+                error!(
+                    "Tool call: game_apply_action - Error: No game found for id {}",
+                    game_id
+                );
                 McpError::invalid_params(
                     format!("No game found for id {}", game_id),
                     None, // data
                 )
             })?;
 
-        let updated = apply_action(&current, &action);
-        let game_id_clone = game_id.clone();
-        games.insert(game_id, updated.clone());
+        self.subscriptions
+            .publish(Update::diff(&game_id, &current, &updated))
+            .await;
 
+        info!(
+            "Tool call: game_apply_action - Output: updated state for game_id={}",
+            game_id
+        );
         let content =
             Content::json(&updated).map_err(|e| McpError::internal_error(e.to_string(), None))?;
-        
-        info!("Tool call: game_apply_action - Output: updated state for game_id={}", game_id_clone);
+        Ok(CallToolResult::success(vec![content]))
+    }
+
+    /// Replay the updates a subscriber missed while disconnected.
+    #[tool(
+        description = "Replay the Update events for a game since the given seq cursor, for clients reconnecting to a resource subscription"
+    )]
+    async fn game_replay_updates(
+        &self,
+        params: Parameters<ReplayUpdatesParams>,
+    ) -> Result<CallToolResult, McpError> {
+        let ReplayUpdatesParams { game_id, since_seq } = params.0;
+        info!(
+            "Tool call: game_replay_updates - Input: game_id={}, since_seq={}",
+            game_id, since_seq
+        );
+
+        let updates = self.subscriptions.updates_since(&game_id, since_seq).await;
+
+        let content =
+            Content::json(&updates).map_err(|e| McpError::internal_error(e.to_string(), None))?;
         Ok(CallToolResult::success(vec![content]))
     }
 }
@@ -135,13 +216,104 @@ impl ServerHandler for TreasureEngine {
         ServerInfo {
             instructions: Some(
                 "A simple Treasure Quest game engine. Start a game with `game_start`, \
-                 then use `game_apply_action` with actions like move/inspect/pickup/use_item/attack."
+                 then use `game_apply_action` with actions like move/inspect/pickup/use_item/attack/eat/drink, \
+                 or go_to/enqueue/step to queue and auto-run multi-step sequences. Each game is also \
+                 exposed as the resource `game://{game_id}`: subscribe to it to receive a \
+                 resource-updated notification (carrying only the URI) after every action or \
+                 background tick, then call `game_replay_updates` with your last-seen seq to fetch \
+                 the Update events describing what changed, including after a reconnect."
                     .to_string(),
             ),
-            capabilities: ServerCapabilities::builder().enable_tools().build(),
+            capabilities: ServerCapabilities::builder()
+                .enable_tools()
+                .enable_resources()
+                .build(),
             ..Default::default()
         }
     }
+
+    /// List every in-progress game as a `game://{game_id}` resource.
+    async fn list_resources(
+        &self,
+        _request: Option<rmcp::model::PaginatedRequestParam>,
+        _context: RequestContext<RoleServer>,
+    ) -> Result<ListResourcesResult, McpError> {
+        let games = self
+            .store
+            .list()
+            .await
+            .map_err(|e| McpError::internal_error(e.to_string(), None))?;
+
+        let resources = games
+            .into_iter()
+            .map(|game| {
+                RawResource::new(resource_uri(&game.game_id), game.game_id.clone()).no_annotation()
+            })
+            .collect();
+
+        Ok(ListResourcesResult {
+            resources,
+            next_cursor: None,
+        })
+    }
+
+    /// Read the current `GameState` for a `game://{game_id}` resource.
+    async fn read_resource(
+        &self,
+        request: ReadResourceRequestParam,
+        _context: RequestContext<RoleServer>,
+    ) -> Result<ReadResourceResult, McpError> {
+        let game_id = request
+            .uri
+            .strip_prefix("game://")
+            .ok_or_else(|| McpError::invalid_params("not a game:// resource", None))?;
+
+        let state = self
+            .store
+            .load(game_id)
+            .await
+            .map_err(|e| McpError::internal_error(e.to_string(), None))?
+            .ok_or_else(|| {
+                McpError::invalid_params(format!("No game found for id {}", game_id), None)
+            })?;
+
+        let text = serde_json::to_string(&state)
+            .map_err(|e| McpError::internal_error(e.to_string(), None))?;
+
+        Ok(ReadResourceResult {
+            contents: vec![ResourceContents::text(text, request.uri)],
+        })
+    }
+
+    /// Subscribe the requesting peer to `Update` notifications for a game.
+    async fn subscribe(
+        &self,
+        request: SubscribeRequestParam,
+        context: RequestContext<RoleServer>,
+    ) -> Result<(), McpError> {
+        let game_id = request
+            .uri
+            .strip_prefix("game://")
+            .ok_or_else(|| McpError::invalid_params("not a game:// resource", None))?;
+
+        self.subscriptions.subscribe(game_id, context.peer).await;
+        Ok(())
+    }
+
+    /// Unsubscribe the requesting peer from a game's `Update` notifications.
+    async fn unsubscribe(
+        &self,
+        request: UnsubscribeRequestParam,
+        context: RequestContext<RoleServer>,
+    ) -> Result<(), McpError> {
+        let game_id = request
+            .uri
+            .strip_prefix("game://")
+            .ok_or_else(|| McpError::invalid_params("not a game:// resource", None))?;
+
+        self.subscriptions.unsubscribe(game_id, &context.peer).await;
+        Ok(())
+    }
 }
 
 #[tokio::main]
@@ -150,10 +322,72 @@ async fn main() -> Result<(), Box<dyn Error>> {
         .with_max_level(tracing::Level::INFO)
         .with_writer(std::io::stderr) // important: logs to stderr, not stdout
         .finish();
-    tracing::subscriber::set_global_default(subscriber)
-        .expect("setting default subscriber failed");
+    tracing::subscriber::set_global_default(subscriber).expect("setting default subscriber failed");
+
+    // Durability is picked via config: a DATABASE_URL means games survive a
+    // restart in Postgres, otherwise we fall back to the in-memory store.
+    let store: Arc<dyn GameStore> = match std::env::var("DATABASE_URL") {
+        Ok(database_url) => {
+            info!("Using PostgresStore for game persistence");
+            Arc::new(PostgresStore::connect(&database_url).await?)
+        }
+        Err(_) => {
+            info!("DATABASE_URL not set, using InMemoryStore for game persistence");
+            Arc::new(InMemoryStore::new())
+        }
+    };
+
+    // Shared with the background ticker below so a tick's Update reaches
+    // the same subscribers as one raised by game_apply_action.
+    let subscriptions = SubscriptionRegistry::new();
+
+    // Background clock: periodically ages hunger/thirst for every game even
+    // if its players never call game_apply_action.
+    {
+        let store = Arc::clone(&store);
+        let subscriptions = subscriptions.clone();
+        tokio::spawn(async move {
+            let mut interval =
+                tokio::time::interval(std::time::Duration::from_secs(TICK_SECONDS as u64));
+            loop {
+                interval.tick().await;
+                let games = match store.list().await {
+                    Ok(games) => games,
+                    Err(e) => {
+                        error!("Failed to list games for background tick: {}", e);
+                        continue;
+                    }
+                };
+
+                let now = now_unix();
+                for game in games {
+                    if game.game_over {
+                        continue;
+                    }
+                    match store
+                        .update(&game.game_id, Box::new(move |state| tick(state, now)))
+                        .await
+                    {
+                        Ok(Some((before, after))) if before.seq != after.seq => {
+                            subscriptions
+                                .publish(Update::diff(&after.game_id, &before, &after))
+                                .await;
+                        }
+                        Ok(Some(_)) => {}
+                        Ok(None) => {
+                            error!("Ticked game {} vanished mid-tick", game.game_id);
+                        }
+                        Err(e) => {
+                            error!("Failed to tick game {}: {}", game.game_id, e);
+                        }
+                    }
+                }
+            }
+        });
+    }
+
     // Run the server over stdio (works with your TS gateway)
-    let service = TreasureEngine::new()
+    let service = TreasureEngine::new(DEFAULT_WORLD_PATH, store, subscriptions)?
         .serve(stdio())
         .await
         .inspect_err(|e| {
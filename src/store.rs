@@ -0,0 +1,174 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use sqlx::PgPool;
+use tokio::sync::Mutex;
+
+use crate::game::GameState;
+
+/// Error type shared by all `GameStore` implementations.
+pub type StoreError = Box<dyn std::error::Error + Send + Sync>;
+
+/// Persistence backend for game state, so operators can pick durability
+/// via config instead of the engine hardcoding an in-memory map.
+#[async_trait]
+pub trait GameStore: Send + Sync {
+    async fn load(&self, game_id: &str) -> Result<Option<GameState>, StoreError>;
+    async fn save(&self, game_id: &str, state: &GameState) -> Result<(), StoreError>;
+    async fn list(&self) -> Result<Vec<GameState>, StoreError>;
+
+    /// Atomically load the game for `game_id`, apply `f` to it, and persist
+    /// the result, so two concurrent mutators of the same game (e.g. two
+    /// `game_apply_action` calls, or one racing the background ticker)
+    /// can't interleave their read-modify-write and clobber each other.
+    /// Returns the state before and after `f` ran, or `None` if no game
+    /// exists for `game_id`.
+    async fn update(
+        &self,
+        game_id: &str,
+        f: Box<dyn FnOnce(&mut GameState) + Send>,
+    ) -> Result<Option<(GameState, GameState)>, StoreError>;
+}
+
+/// Keeps all games in memory; state is lost on restart.
+#[derive(Clone, Default)]
+pub struct InMemoryStore {
+    games: Arc<Mutex<HashMap<String, GameState>>>,
+}
+
+impl InMemoryStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait]
+impl GameStore for InMemoryStore {
+    async fn load(&self, game_id: &str) -> Result<Option<GameState>, StoreError> {
+        Ok(self.games.lock().await.get(game_id).cloned())
+    }
+
+    async fn save(&self, game_id: &str, state: &GameState) -> Result<(), StoreError> {
+        self.games
+            .lock()
+            .await
+            .insert(game_id.to_string(), state.clone());
+        Ok(())
+    }
+
+    async fn list(&self) -> Result<Vec<GameState>, StoreError> {
+        Ok(self.games.lock().await.values().cloned().collect())
+    }
+
+    async fn update(
+        &self,
+        game_id: &str,
+        f: Box<dyn FnOnce(&mut GameState) + Send>,
+    ) -> Result<Option<(GameState, GameState)>, StoreError> {
+        let mut games = self.games.lock().await;
+        let Some(state) = games.get_mut(game_id) else {
+            return Ok(None);
+        };
+
+        let before = state.clone();
+        f(state);
+        let after = state.clone();
+        Ok(Some((before, after)))
+    }
+}
+
+/// Stores game state as JSONB rows in Postgres so games survive a restart.
+#[derive(Clone)]
+pub struct PostgresStore {
+    pool: PgPool,
+}
+
+impl PostgresStore {
+    /// Connect to `database_url` and ensure the backing table exists.
+    pub async fn connect(database_url: &str) -> Result<Self, StoreError> {
+        let pool = PgPool::connect(database_url).await?;
+        sqlx::query(
+            "CREATE TABLE IF NOT EXISTS games (
+                game_id TEXT PRIMARY KEY,
+                state JSONB NOT NULL
+            )",
+        )
+        .execute(&pool)
+        .await?;
+        Ok(Self { pool })
+    }
+}
+
+#[async_trait]
+impl GameStore for PostgresStore {
+    async fn load(&self, game_id: &str) -> Result<Option<GameState>, StoreError> {
+        let row: Option<(serde_json::Value,)> =
+            sqlx::query_as("SELECT state FROM games WHERE game_id = $1")
+                .bind(game_id)
+                .fetch_optional(&self.pool)
+                .await?;
+
+        Ok(match row {
+            Some((value,)) => Some(serde_json::from_value(value)?),
+            None => None,
+        })
+    }
+
+    async fn save(&self, game_id: &str, state: &GameState) -> Result<(), StoreError> {
+        let value = serde_json::to_value(state)?;
+        sqlx::query(
+            "INSERT INTO games (game_id, state) VALUES ($1, $2)
+             ON CONFLICT (game_id) DO UPDATE SET state = EXCLUDED.state",
+        )
+        .bind(game_id)
+        .bind(value)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    async fn list(&self) -> Result<Vec<GameState>, StoreError> {
+        let rows: Vec<(serde_json::Value,)> = sqlx::query_as("SELECT state FROM games")
+            .fetch_all(&self.pool)
+            .await?;
+
+        rows.into_iter()
+            .map(|(value,)| serde_json::from_value(value).map_err(Into::into))
+            .collect()
+    }
+
+    async fn update(
+        &self,
+        game_id: &str,
+        f: Box<dyn FnOnce(&mut GameState) + Send>,
+    ) -> Result<Option<(GameState, GameState)>, StoreError> {
+        let mut tx = self.pool.begin().await?;
+
+        let row: Option<(serde_json::Value,)> =
+            sqlx::query_as("SELECT state FROM games WHERE game_id = $1 FOR UPDATE")
+                .bind(game_id)
+                .fetch_optional(&mut *tx)
+                .await?;
+
+        let Some((value,)) = row else {
+            tx.rollback().await?;
+            return Ok(None);
+        };
+
+        let mut state: GameState = serde_json::from_value(value)?;
+        let before = state.clone();
+        f(&mut state);
+        let after = state.clone();
+
+        let new_value = serde_json::to_value(&after)?;
+        sqlx::query("UPDATE games SET state = $2 WHERE game_id = $1")
+            .bind(game_id)
+            .bind(new_value)
+            .execute(&mut *tx)
+            .await?;
+
+        tx.commit().await?;
+        Ok(Some((before, after)))
+    }
+}
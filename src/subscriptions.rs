@@ -0,0 +1,84 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use rmcp::model::ResourceUpdatedNotificationParam;
+use rmcp::service::{Peer, RoleServer};
+use tokio::sync::Mutex;
+
+use crate::update::Update;
+
+/// How many past updates to keep per game so a reconnecting client can
+/// replay whatever it missed instead of only seeing new events.
+const REPLAY_BUFFER: usize = 50;
+
+/// Tracks which peers are subscribed to each game's `game://{game_id}`
+/// resource, plus a short replay buffer of recent `Update`s per game.
+#[derive(Clone, Default)]
+pub struct SubscriptionRegistry {
+    subscribers: Arc<Mutex<HashMap<String, Vec<Peer<RoleServer>>>>>,
+    history: Arc<Mutex<HashMap<String, Vec<Update>>>>,
+}
+
+impl SubscriptionRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register `peer` as subscribed to `game_id`'s resource updates.
+    pub async fn subscribe(&self, game_id: &str, peer: Peer<RoleServer>) {
+        self.subscribers
+            .lock()
+            .await
+            .entry(game_id.to_string())
+            .or_default()
+            .push(peer);
+    }
+
+    /// Stop notifying `peer` about `game_id`.
+    pub async fn unsubscribe(&self, game_id: &str, peer: &Peer<RoleServer>) {
+        if let Some(peers) = self.subscribers.lock().await.get_mut(game_id) {
+            peers.retain(|p| p != peer);
+        }
+    }
+
+    /// Record `update` in the replay buffer and notify every subscriber of
+    /// `update.game_id` that the `game://{game_id}` resource changed.
+    pub async fn publish(&self, update: Update) {
+        let game_id = update.game_id.clone();
+
+        {
+            let mut history = self.history.lock().await;
+            let log = history.entry(game_id.clone()).or_default();
+            log.push(update);
+            if log.len() > REPLAY_BUFFER {
+                log.remove(0);
+            }
+        }
+
+        let subscribers = self.subscribers.lock().await;
+        if let Some(peers) = subscribers.get(&game_id) {
+            let uri = format!("game://{}", game_id);
+            for peer in peers {
+                let _ = peer
+                    .notify_resource_updated(ResourceUpdatedNotificationParam { uri: uri.clone() })
+                    .await;
+            }
+        }
+    }
+
+    /// Updates recorded for `game_id` with `seq` greater than `since_seq`,
+    /// oldest first, for a client replaying after a reconnect.
+    pub async fn updates_since(&self, game_id: &str, since_seq: u64) -> Vec<Update> {
+        self.history
+            .lock()
+            .await
+            .get(game_id)
+            .map(|log| {
+                log.iter()
+                    .filter(|update| update.seq > since_seq)
+                    .cloned()
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+}
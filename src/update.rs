@@ -0,0 +1,40 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::game::{GameState, PlayerState};
+
+/// The delta a single `apply_action` (or background `tick`) made to a game,
+/// so a subscriber can react to what changed instead of diffing the whole
+/// `GameState` itself.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct Update {
+    pub game_id: String,
+    /// Matches the resulting `GameState::seq`; clients replaying after a
+    /// reconnect ask for everything with `seq` greater than their cursor.
+    pub seq: u64,
+    /// Log lines appended by this action.
+    pub new_log_lines: Vec<String>,
+    /// The player's new stats, present only if something about them changed.
+    #[serde(default)]
+    pub player: Option<PlayerState>,
+    /// Present if `game_over` flipped during this action.
+    #[serde(default)]
+    pub game_over: Option<bool>,
+    /// Present if `victory` flipped during this action.
+    #[serde(default)]
+    pub victory: Option<bool>,
+}
+
+impl Update {
+    /// Compute the delta between a game's state before and after an action.
+    pub fn diff(game_id: &str, before: &GameState, after: &GameState) -> Self {
+        Self {
+            game_id: game_id.to_string(),
+            seq: after.seq,
+            new_log_lines: after.log[before.log.len()..].to_vec(),
+            player: (before.player != after.player).then(|| after.player.clone()),
+            game_over: (before.game_over != after.game_over).then_some(after.game_over),
+            victory: (before.victory != after.victory).then_some(after.victory),
+        }
+    }
+}
@@ -0,0 +1,181 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::error::Error;
+use std::path::Path;
+
+use crate::game::Direction;
+
+/// A stat on `PlayerState` that an effect can change.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StatKind {
+    Health,
+    Hunger,
+    Thirst,
+}
+
+/// A tile coordinate, used by effects that care about where the player is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Location {
+    pub x: i32,
+    pub y: i32,
+}
+
+/// Something using an item can do to the game, replacing one-off item logic
+/// hardcoded per item name.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type")]
+pub enum Effect {
+    /// Adjust a player stat by `delta`, optionally clamped to `(min, max)`.
+    #[serde(rename = "change_parameter")]
+    ChangeParameter {
+        param: StatKind,
+        delta: i32,
+        #[serde(default)]
+        clamp: Option<(i32, i32)>,
+    },
+    /// Remove the item being used from the player's inventory.
+    #[serde(rename = "consume_self")]
+    ConsumeSelf,
+    /// If the player is at `(x, y)`, grant them the listed items.
+    #[serde(rename = "unlock_at")]
+    UnlockAt {
+        x: i32,
+        y: i32,
+        reveals: Vec<ItemDef>,
+    },
+    /// End the game in victory.
+    #[serde(rename = "grant_victory")]
+    GrantVictory,
+}
+
+/// An item that can be found and picked up somewhere in the world.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ItemDef {
+    pub id: String,
+    pub name: String,
+    pub pickup_text: String,
+    /// Where the player must be standing to use this item, if anywhere in
+    /// particular (e.g. a key that only opens a chest in one room).
+    #[serde(default)]
+    pub use_requires: Option<Location>,
+    /// Log line(s) printed when the item is used, before its effects apply.
+    #[serde(default)]
+    pub use_text: Vec<String>,
+    /// Log line printed when `use_requires` isn't met.
+    #[serde(default)]
+    pub use_elsewhere_text: Option<String>,
+    #[serde(default)]
+    pub effects: Vec<Effect>,
+}
+
+/// A single map tile, loaded from world data.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Tile {
+    pub x: i32,
+    pub y: i32,
+    pub description: String,
+    #[serde(default)]
+    pub items: Vec<ItemDef>,
+    #[serde(default)]
+    pub exits: Vec<Direction>,
+}
+
+/// A creature guarding a tile, fought with `attack`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Npc {
+    pub id: String,
+    pub name: String,
+    pub location: Location,
+    pub health: i32,
+    pub attack: i32,
+    pub defense: i32,
+    /// Item names dropped onto the tile once the NPC is defeated.
+    #[serde(default)]
+    pub loot: Vec<String>,
+}
+
+/// Top-level shape of the world data file.
+#[derive(Debug, Deserialize)]
+struct WorldData {
+    tiles: Vec<Tile>,
+    #[serde(default)]
+    npcs: Vec<Npc>,
+}
+
+/// The full set of tiles, exits, items, and NPCs for an adventure.
+///
+/// Loaded once at startup from a JSON file so different maps can be
+/// shipped without recompiling the engine.
+#[derive(Debug, Clone, Default)]
+pub struct World {
+    tiles: HashMap<(i32, i32), Tile>,
+    npcs: HashMap<(i32, i32), Npc>,
+    /// Every item definition in the world, keyed by name, including ones
+    /// only reachable via an `UnlockAt` effect's `reveals` rather than
+    /// sitting directly on a tile.
+    items: HashMap<String, ItemDef>,
+}
+
+/// Index `items` by name into `out`, recursing into any `UnlockAt` effect's
+/// `reveals` so items only reachable that way are still looked up by name.
+fn index_items(items: &[ItemDef], out: &mut HashMap<String, ItemDef>) {
+    for item in items {
+        for effect in &item.effects {
+            if let Effect::UnlockAt { reveals, .. } = effect {
+                index_items(reveals, out);
+            }
+        }
+        out.insert(item.name.clone(), item.clone());
+    }
+}
+
+impl World {
+    /// Load a world definition from a JSON file describing tiles and NPCs.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, Box<dyn Error>> {
+        let data = std::fs::read_to_string(path)?;
+        let world_data: WorldData = serde_json::from_str(&data)?;
+
+        let mut items = HashMap::new();
+        for tile in &world_data.tiles {
+            index_items(&tile.items, &mut items);
+        }
+
+        Ok(Self {
+            tiles: world_data
+                .tiles
+                .into_iter()
+                .map(|t| ((t.x, t.y), t))
+                .collect(),
+            npcs: world_data
+                .npcs
+                .into_iter()
+                .map(|n| ((n.location.x, n.location.y), n))
+                .collect(),
+            items,
+        })
+    }
+
+    /// The tile at the given coordinates, if one is defined.
+    pub fn tile_at(&self, x: i32, y: i32) -> Option<&Tile> {
+        self.tiles.get(&(x, y))
+    }
+
+    /// Human-readable description of the tile at the given coordinates.
+    pub fn describe(&self, x: i32, y: i32) -> &str {
+        self.tile_at(x, y)
+            .map(|t| t.description.as_str())
+            .unwrap_or("featureless terrain.")
+    }
+
+    /// Look up an item definition by name, anywhere in the world — on a
+    /// tile or only revealed later via an `UnlockAt` effect.
+    pub fn item_by_name(&self, name: &str) -> Option<&ItemDef> {
+        self.items.get(name)
+    }
+
+    /// The NPC guarding the tile at the given coordinates, if any.
+    pub fn npc_at(&self, x: i32, y: i32) -> Option<&Npc> {
+        self.npcs.get(&(x, y))
+    }
+}